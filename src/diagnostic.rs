@@ -0,0 +1,14 @@
+//! Source locations and diagnostics.
+
+/// A half-open byte range `start..end` into a source buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Loc {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Loc {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
@@ -0,0 +1,7 @@
+//! The language frontend: the parser and source-level query APIs built on top
+//! of its [`Ast`](parser::ast::Ast).
+
+pub mod parser;
+pub mod signature;
+
+pub use signature::{call_info, CallInfo};
@@ -6,6 +6,7 @@ use crate::diagnostic::Loc;
 ///
 /// `Set`, `List` and `Dict` are three special classes that should be implemented by code generator
 /// backend. Specially, `Any` means any type except `Nil` is possible.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum _Type {
     Any,
     Float,
@@ -16,6 +17,49 @@ pub enum _Type {
     Nil,
 }
 
+impl _Type {
+    /// The type model used by type ascriptions and casts.
+    ///
+    /// [`from_id`](Self::from_id) resolves a source type name and
+    /// [`accepts`](Self::accepts) encodes the coercion/acceptance relation the
+    /// evaluator applies. The surface parsing of `as`/`:` and the value-level
+    /// enforcement against runtime values are performed by the `vm` backend,
+    /// which is not part of this snapshot; here the relation is modelled and
+    /// unit-tested over `_Type` alone.
+    ///
+    /// Resolve a type name written in source (after `as` or `:`) into a
+    /// [`_Type`]. The built-in names map to their variants; any other
+    /// identifier names a class and resolves to [`_Type::Class`].
+    pub fn from_id(name: &str) -> Self {
+        match name {
+            "Any" => _Type::Any,
+            "Float" => _Type::Float,
+            "Int" => _Type::Int,
+            "Str" => _Type::Str,
+            "Function" => _Type::Function,
+            "Nil" => _Type::Nil,
+            other => _Type::Class(other.to_string()),
+        }
+    }
+
+    /// Whether a value of type `value` satisfies `self` where `self` is the
+    /// required type, following the checks the backend performs at runtime:
+    /// `Nil` satisfies only an explicit `Nil`, `Any` accepts any other value,
+    /// `Int` and `Float` coerce to each other, anything non-`Nil` converts to
+    /// `Str`, and a class matches the same class by name.
+    pub fn accepts(&self, value: &_Type) -> bool {
+        use _Type::*;
+        match (self, value) {
+            (_, Nil) => matches!(self, Nil),
+            (Any, _) => true,
+            (Int, Float) | (Float, Int) => true,
+            (Str, _) => true,
+            (Class(a), Class(b)) => a == b,
+            (a, b) => a == b,
+        }
+    }
+}
+
 pub enum Stat_ {
     Expr(Expr),
     Continue,
@@ -24,11 +68,101 @@ pub enum Stat_ {
     Class(String, Vec<(String, Loc)>, Vec<Expr>),
     /// An optional break condition & a body
     Loop(Option<Expr>, Vec<Stat>),
-    /// variables, iterator, statements
-    For(Box<Expr>, Box<Expr>, Vec<Stat>),
+    /// binding pattern, iterator, statements
+    For(Pattern, Box<Expr>, Vec<Stat>),
     Error,
 }
 
+/// A binding pattern used in `for` and assignment position.
+///
+/// A dedicated node — rather than overloading [`Expr`] — keeps binding
+/// positions unambiguous and lets the backend emit the unpacking sequence
+/// directly.
+pub enum Pattern_ {
+    /// Bind to a single identifier.
+    Id(String),
+    /// A wildcard `_` that matches and discards its value.
+    Wildcard,
+    /// Tuple/list destructuring, e.g. `a, b` or `(x, _, z)`. Elements may
+    /// themselves be patterns, allowing nested destructuring.
+    Tuple(Vec<Pattern>),
+}
+
+pub struct Pattern {
+    pub loc: Loc,
+    pub val: Pattern_,
+}
+
+impl Debug for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.val {
+            Pattern_::Id(id) => write!(f, "{:?}", id),
+            Pattern_::Wildcard => write!(f, "_"),
+            Pattern_::Tuple(items) => f.debug_list().entries(items.iter()).finish(),
+        }
+    }
+}
+
+impl Pattern {
+    pub fn new(val: Pattern_, loc: Loc) -> Self {
+        Self { loc, val }
+    }
+
+    /// See [`Ast::eq_ignore_loc`].
+    pub fn eq_ignore_loc(&self, other: &Self) -> bool {
+        match (&self.val, &other.val) {
+            (Pattern_::Id(a), Pattern_::Id(b)) => a == b,
+            (Pattern_::Wildcard, Pattern_::Wildcard) => true,
+            (Pattern_::Tuple(a), Pattern_::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.eq_ignore_loc(y))
+            }
+            _ => false,
+        }
+    }
+
+    /// Lower an expression parsed in binding position into a [`Pattern`].
+    ///
+    /// An identifier becomes [`Pattern_::Id`] (the bare `_` becomes
+    /// [`Pattern_::Wildcard`]), a comma chain — optionally parenthesised —
+    /// becomes a [`Pattern_::Tuple`] of lowered elements, and anything else is
+    /// not a valid binding target, returned as `Err` with the offending `Loc`.
+    pub fn from_expr(expr: Expr) -> Result<Self, Loc> {
+        let Expr { loc, val } = expr;
+        match val {
+            Expr_::Id(name) => {
+                let val = if name == "_" {
+                    Pattern_::Wildcard
+                } else {
+                    Pattern_::Id(name)
+                };
+                Ok(Pattern { loc, val })
+            }
+            Expr_::Parentheses(inner) => Pattern::from_expr(*inner),
+            Expr_::Infix(OpInfix::Comma, lhs, rhs) => {
+                let mut items = Vec::new();
+                flatten_comma(*lhs, &mut items)?;
+                flatten_comma(*rhs, &mut items)?;
+                Ok(Pattern {
+                    loc,
+                    val: Pattern_::Tuple(items),
+                })
+            }
+            _ => Err(loc),
+        }
+    }
+}
+
+/// Flatten a comma chain into a list of patterns, lowering each element.
+fn flatten_comma(expr: Expr, out: &mut Vec<Pattern>) -> Result<(), Loc> {
+    if let Expr_::Infix(OpInfix::Comma, lhs, rhs) = expr.val {
+        flatten_comma(*lhs, out)?;
+        flatten_comma(*rhs, out)
+    } else {
+        out.push(Pattern::from_expr(expr)?);
+        Ok(())
+    }
+}
+
 pub struct Stat {
     pub loc: Loc,
     pub val: Stat_,
@@ -74,9 +208,8 @@ impl Stat {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum OpInfix {
-    Assign,
     Range,
     Or,
     And,
@@ -97,13 +230,13 @@ pub enum OpInfix {
     Member,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum OpPrefix {
     Not,
     Neg,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum OpPostfix {
     Index,
     Call,
@@ -117,14 +250,43 @@ pub enum Expr_ {
     If(Vec<Expr>),
     Prefix(OpPrefix, Box<Expr>),
     Call(Box<Expr>, Vec<Expr>),
+    /// A method call `receiver.method(args..)`
+    ///
+    /// Receiver, method name and the argument list. Unlike `Call` over an
+    /// `Infix(Member, ..)`, this keeps the dispatch intent explicit so the
+    /// backend can load the receiver once and bind `self`.
+    MethodCall(Box<Expr>, String, Vec<Expr>),
     Index(Box<Expr>, Box<Expr>),
     Infix(OpInfix, Box<Expr>, Box<Expr>),
     /// Define a function
     ///
     /// First expression is declaration(None for no parameters), second is function body
     /// If its name is None, then this is a lambda expression
-    Def(Option<String>, Vec<String>, Vec<Stat>, Vec<(String, Expr)>),
+    ///
+    /// Each parameter optionally carries a [`_Type`] ascription (`None` when the
+    /// parameter is left untyped).
+    Def(
+        Option<String>,
+        Vec<(String, Option<_Type>)>,
+        Vec<Stat>,
+        Vec<(String, Expr)>,
+    ),
     Id(String),
+    /// An assignment `pattern = value`
+    ///
+    /// The binding side is a [`Pattern`] so tuple/list destructuring such as
+    /// `x, y = point` is represented directly; [`OpInfix`] has no assignment
+    /// operator.
+    Assign(Pattern, Box<Expr>),
+    /// A type-ascription cast `expr as Type`
+    Cast(Box<Expr>, _Type),
+    /// A host-registered custom syntax node
+    ///
+    /// Produced when the parser matches a registered custom infix operator or
+    /// syntax rule. The `String` names the rule and the `Vec<Expr>` carries the
+    /// matched expression slots in order; the backend dispatches these to the
+    /// host callback registered under that name.
+    Custom(String, Vec<Expr>),
     Parentheses(Box<Expr>),
     Const(Const),
     Error,
@@ -135,6 +297,89 @@ pub struct Expr {
     pub val: Expr_,
 }
 
+impl Expr {
+    /// Build an assignment `lhs = rhs`, lowering the left-hand side into a
+    /// binding [`Pattern`]. Returns `Err` with the offending `Loc` when the
+    /// left-hand side is not a valid binding target.
+    ///
+    /// The parser calls this (and lowers `for` binders with
+    /// [`Pattern::from_expr`]) to put patterns in binding position. The
+    /// unpacking sequence that indexes the value into each slot and errors on
+    /// an arity mismatch is emitted by the `vm` backend, which is not part of
+    /// this snapshot.
+    pub fn assign(lhs: Expr, rhs: Expr, loc: Loc) -> Result<Self, Loc> {
+        let pat = Pattern::from_expr(lhs)?;
+        Ok(Expr {
+            loc,
+            val: Expr_::Assign(pat, Box::new(rhs)),
+        })
+    }
+
+    /// Lower a `Call` over a `Member` access into a first-class
+    /// [`Expr_::MethodCall`].
+    ///
+    /// The parser forms `obj.m(a, b)` as `Call(Infix(Member, obj, m), args)`;
+    /// this recognises the `.ident(` shape and rewrites it so the backend sees
+    /// an explicit method dispatch. Any other call is returned unchanged.
+    ///
+    /// The parser calls this on every freshly-built `Call`; the bound dispatch
+    /// that loads the receiver once and binds `self` is emitted by the `vm`
+    /// backend, which is not part of this snapshot.
+    pub fn into_method_call(self) -> Self {
+        let Expr { loc, val } = self;
+        match val {
+            Expr_::Call(callee, args) => {
+                let Expr {
+                    loc: callee_loc,
+                    val: callee_val,
+                } = *callee;
+                match callee_val {
+                    Expr_::Infix(OpInfix::Member, receiver, member) => {
+                        let Expr {
+                            loc: member_loc,
+                            val: member_val,
+                        } = *member;
+                        match member_val {
+                            Expr_::Id(method) => Expr {
+                                loc,
+                                val: Expr_::MethodCall(receiver, method, args),
+                            },
+                            member_val => Expr {
+                                loc,
+                                val: Expr_::Call(
+                                    Box::new(Expr {
+                                        loc: callee_loc,
+                                        val: Expr_::Infix(
+                                            OpInfix::Member,
+                                            receiver,
+                                            Box::new(Expr {
+                                                loc: member_loc,
+                                                val: member_val,
+                                            }),
+                                        ),
+                                    }),
+                                    args,
+                                ),
+                            },
+                        }
+                    }
+                    callee_val => Expr {
+                        loc,
+                        val: Expr_::Call(
+                            Box::new(Expr {
+                                loc: callee_loc,
+                                val: callee_val,
+                            }),
+                            args,
+                        ),
+                    },
+                }
+            }
+            val => Expr { loc, val },
+        }
+    }
+}
+
 impl Debug for Expr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.val {
@@ -142,6 +387,9 @@ impl Debug for Expr {
             Expr_::Prefix(op, expr) => f.debug_tuple("").field(&op).field(&expr).finish(),
             Expr_::Infix(op, e1, e2) => f.debug_tuple("").field(&e1).field(&op).field(&e2).finish(),
             Expr_::Id(id) => write!(f, "{:?}", id),
+            Expr_::Custom(name, slots) => {
+                f.debug_tuple("Custom").field(name).field(slots).finish()
+            }
             Expr_::Const(c) => write!(f, "{:?}", c),
             Expr_::Error => write!(f, "Error"),
             Expr_::If(v) => f.debug_tuple("").field(&"if").field(&v).finish(),
@@ -160,7 +408,25 @@ impl Debug for Expr {
                 .field(&")")
                 .finish(),
             Expr_::Call(expr, call) => f.debug_tuple("Call").field(expr).field(call).finish(),
+            Expr_::MethodCall(receiver, method, args) => f
+                .debug_tuple("MethodCall")
+                .field(receiver)
+                .field(method)
+                .field(args)
+                .finish(),
             Expr_::Index(expr, index) => f.debug_tuple("Call").field(expr).field(index).finish(),
+            Expr_::Assign(pat, value) => f
+                .debug_tuple("")
+                .field(pat)
+                .field(&"=")
+                .field(value)
+                .finish(),
+            Expr_::Cast(expr, ty) => f
+                .debug_tuple("")
+                .field(expr)
+                .field(&"as")
+                .field(ty)
+                .finish(),
         }
     }
 }
@@ -192,7 +458,237 @@ impl Debug for Const {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Debug)]
 pub struct Ast {
     pub statements: Vec<Stat>,
 }
+
+fn stats_eq_ignore_loc(a: &[Stat], b: &[Stat]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.eq_ignore_loc(y))
+}
+
+fn exprs_eq_ignore_loc(a: &[Expr], b: &[Expr]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.eq_ignore_loc(y))
+}
+
+impl Ast {
+    /// Compare two trees for structural equality, ignoring every `loc` field.
+    ///
+    /// This is the basis of [`assert_ast_eq!`](crate::assert_ast_eq) and lets
+    /// golden parser tests be written without reproducing exact byte offsets.
+    pub fn eq_ignore_loc(&self, other: &Self) -> bool {
+        stats_eq_ignore_loc(&self.statements, &other.statements)
+    }
+}
+
+impl Stat {
+    /// See [`Ast::eq_ignore_loc`].
+    pub fn eq_ignore_loc(&self, other: &Self) -> bool {
+        use Stat_::*;
+        match (&self.val, &other.val) {
+            (Expr(a), Expr(b)) => a.eq_ignore_loc(b),
+            (Continue, Continue) | (Break, Break) | (Error, Error) => true,
+            (Return(a), Return(b)) => match (a, b) {
+                (Some(a), Some(b)) => a.eq_ignore_loc(b),
+                (None, None) => true,
+                _ => false,
+            },
+            (Class(n1, f1, m1), Class(n2, f2, m2)) => {
+                n1 == n2
+                    && f1.len() == f2.len()
+                    && f1.iter().zip(f2).all(|(a, b)| a.0 == b.0)
+                    && exprs_eq_ignore_loc(m1, m2)
+            }
+            (Loop(c1, b1), Loop(c2, b2)) => {
+                let cond = match (c1, c2) {
+                    (Some(a), Some(b)) => a.eq_ignore_loc(b),
+                    (None, None) => true,
+                    _ => false,
+                };
+                cond && stats_eq_ignore_loc(b1, b2)
+            }
+            (For(v1, i1, b1), For(v2, i2, b2)) => {
+                v1.eq_ignore_loc(v2) && i1.eq_ignore_loc(i2) && stats_eq_ignore_loc(b1, b2)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Expr {
+    /// See [`Ast::eq_ignore_loc`].
+    pub fn eq_ignore_loc(&self, other: &Self) -> bool {
+        use Expr_::*;
+        match (&self.val, &other.val) {
+            (Block(a), Block(b)) => stats_eq_ignore_loc(a, b),
+            (If(a), If(b)) => exprs_eq_ignore_loc(a, b),
+            (Prefix(o1, e1), Prefix(o2, e2)) => o1 == o2 && e1.eq_ignore_loc(e2),
+            (Call(c1, a1), Call(c2, a2)) => c1.eq_ignore_loc(c2) && exprs_eq_ignore_loc(a1, a2),
+            (MethodCall(r1, m1, a1), MethodCall(r2, m2, a2)) => {
+                r1.eq_ignore_loc(r2) && m1 == m2 && exprs_eq_ignore_loc(a1, a2)
+            }
+            (Index(e1, i1), Index(e2, i2)) => e1.eq_ignore_loc(e2) && i1.eq_ignore_loc(i2),
+            (Infix(o1, a1, b1), Infix(o2, a2, b2)) => {
+                o1 == o2 && a1.eq_ignore_loc(a2) && b1.eq_ignore_loc(b2)
+            }
+            (Def(n1, d1, b1, bind1), Def(n2, d2, b2, bind2)) => {
+                n1 == n2
+                    && d1 == d2
+                    && stats_eq_ignore_loc(b1, b2)
+                    && bind1.len() == bind2.len()
+                    && bind1
+                        .iter()
+                        .zip(bind2)
+                        .all(|(a, b)| a.0 == b.0 && a.1.eq_ignore_loc(&b.1))
+            }
+            (Id(a), Id(b)) => a == b,
+            (Assign(p1, v1), Assign(p2, v2)) => p1.eq_ignore_loc(p2) && v1.eq_ignore_loc(v2),
+            (Cast(e1, t1), Cast(e2, t2)) => e1.eq_ignore_loc(e2) && t1 == t2,
+            (Custom(n1, s1), Custom(n2, s2)) => n1 == n2 && exprs_eq_ignore_loc(s1, s2),
+            (Parentheses(a), Parentheses(b)) => a.eq_ignore_loc(b),
+            (Const(a), Const(b)) => a.eq_ignore_loc(b),
+            (Error, Error) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Const {
+    /// See [`Ast::eq_ignore_loc`].
+    pub fn eq_ignore_loc(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Const::Int(a), Const::Int(b)) => a == b,
+            (Const::Float(a), Const::Float(b)) => a == b,
+            (Const::Str(a), Const::Str(b)) => a == b,
+            (Const::Bool(a), Const::Bool(b)) => a == b,
+            (Const::List(a), Const::List(b)) | (Const::Set(a), Const::Set(b)) => {
+                exprs_eq_ignore_loc(a, b)
+            }
+            (Const::Dict(k1, v1), Const::Dict(k2, v2)) => {
+                exprs_eq_ignore_loc(k1, k2) && exprs_eq_ignore_loc(v1, v2)
+            }
+            (Const::Nil, Const::Nil) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Assert that two trees are structurally equal ignoring source locations.
+///
+/// The operands are already-built trees of the same type — an [`Ast`],
+/// [`Stat`], [`Expr`], [`Const`] or [`Pattern`] — and the comparison is
+/// delegated to their `eq_ignore_loc` (see [`Ast::eq_ignore_loc`]), so golden
+/// tests stay readable without reproducing byte offsets by hand. The macro
+/// does not parse source text.
+#[macro_export]
+macro_rules! assert_ast_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        let left = &$left;
+        let right = &$right;
+        assert!(
+            left.eq_ignore_loc(right),
+            "ASTs differ (ignoring loc):\n left: {:#?}\nright: {:#?}",
+            left,
+            right,
+        );
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::Loc;
+
+    /// An expression with a throwaway location.
+    fn e(val: Expr_) -> Expr {
+        Expr {
+            loc: Loc::new(0, 0),
+            val,
+        }
+    }
+
+    fn id(name: &str) -> Expr {
+        e(Expr_::Id(name.into()))
+    }
+
+    fn int(n: i64) -> Expr {
+        e(Expr_::Const(Const::Int(n)))
+    }
+
+    #[test]
+    fn eq_ignore_loc_ignores_spans() {
+        let a = Expr {
+            loc: Loc::new(0, 5),
+            val: Expr_::Infix(OpInfix::Plus, Box::new(int(1)), Box::new(int(2))),
+        };
+        let b = Expr {
+            loc: Loc::new(42, 99),
+            val: Expr_::Infix(OpInfix::Plus, Box::new(int(1)), Box::new(int(2))),
+        };
+        assert_ast_eq!(a, b);
+    }
+
+    #[test]
+    fn eq_ignore_loc_still_distinguishes_structure() {
+        let a = Expr_::Infix(OpInfix::Plus, Box::new(int(1)), Box::new(int(2)));
+        let b = Expr_::Infix(OpInfix::Minus, Box::new(int(1)), Box::new(int(2)));
+        assert!(!e(a).eq_ignore_loc(&e(b)));
+    }
+
+    #[test]
+    fn lowers_member_call_to_method_call() {
+        let member = e(Expr_::Infix(
+            OpInfix::Member,
+            Box::new(id("obj")),
+            Box::new(id("scale")),
+        ));
+        let call = e(Expr_::Call(Box::new(member), vec![int(2)]));
+        let expected = e(Expr_::MethodCall(
+            Box::new(id("obj")),
+            "scale".into(),
+            vec![int(2)],
+        ));
+        assert_ast_eq!(call.into_method_call(), expected);
+    }
+
+    #[test]
+    fn plain_call_is_left_unchanged() {
+        let call = e(Expr_::Call(Box::new(id("foo")), vec![int(1)]));
+        let expected = e(Expr_::Call(Box::new(id("foo")), vec![int(1)]));
+        assert_ast_eq!(call.into_method_call(), expected);
+    }
+
+    #[test]
+    fn pattern_from_comma_chain_and_wildcard() {
+        let expr = e(Expr_::Infix(
+            OpInfix::Comma,
+            Box::new(id("x")),
+            Box::new(id("_")),
+        ));
+        let pat = Pattern::from_expr(expr).unwrap();
+        match pat.val {
+            Pattern_::Tuple(items) => {
+                assert_eq!(items.len(), 2);
+                assert!(matches!(&items[0].val, Pattern_::Id(n) if n == "x"));
+                assert!(matches!(items[1].val, Pattern_::Wildcard));
+            }
+            _ => panic!("expected a tuple pattern"),
+        }
+    }
+
+    #[test]
+    fn pattern_from_invalid_target_is_rejected() {
+        assert!(Pattern::from_expr(int(1)).is_err());
+    }
+
+    #[test]
+    fn type_resolution_and_acceptance() {
+        assert_eq!(_Type::from_id("Int"), _Type::Int);
+        assert_eq!(_Type::from_id("Point"), _Type::Class("Point".into()));
+        // Int <-> Float coerce, Any rejects Nil, classes match by name.
+        assert!(_Type::Float.accepts(&_Type::Int));
+        assert!(!_Type::Any.accepts(&_Type::Nil));
+        assert!(_Type::Class("P".into()).accepts(&_Type::Class("P".into())));
+        assert!(!_Type::Class("P".into()).accepts(&_Type::Class("Q".into())));
+    }
+}
@@ -0,0 +1,164 @@
+//! Host-registered syntax extensions.
+//!
+//! An embedder builds an [`OperatorRegistry`], registers custom infix operators
+//! and leading-keyword syntax rules on it, and hands it to the parser. The
+//! precedence loop is meant to consult the registry when it meets an unknown
+//! identifier-operator (via [`OperatorRegistry::infix`]) or a registered
+//! leading keyword (via [`OperatorRegistry::rule_for`]) and emit an
+//! [`Expr_::Custom`] node (via [`OperatorRegistry::make_custom`]) carrying the
+//! matched slots; the backend then dispatches those to the callbacks the host
+//! registered under the same name.
+//!
+//! This snapshot provides the registry and its lookups/producer, exercised by
+//! the tests below. The Pratt-loop integration and the backend callback
+//! dispatch live in the `parser` and `vm` modules, which are not part of this
+//! snapshot.
+
+use super::ast::{Expr, Expr_};
+use crate::diagnostic::Loc;
+
+/// Associativity of a custom infix operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+/// A custom infix operator registered by the host.
+pub struct InfixOp {
+    /// Name the backend dispatches on.
+    pub name: String,
+    /// The identifier-operator token that triggers this operator, e.g. `|>`.
+    pub symbol: String,
+    /// Binding power used by the precedence loop; higher binds tighter.
+    pub binding_power: u32,
+    /// Associativity, consulted when two operators share a binding power.
+    pub associativity: Associativity,
+}
+
+/// A marker in a custom-syntax token template.
+pub enum TemplateSlot {
+    /// A literal keyword/identifier that must match verbatim.
+    Keyword(String),
+    /// An expression slot captured into the resulting node.
+    Expr,
+    /// An identifier slot captured as a name.
+    Ident,
+}
+
+/// A custom leading-keyword syntax rule.
+pub struct SyntaxRule {
+    /// Name the backend dispatches on.
+    pub name: String,
+    /// The token template, beginning with a [`TemplateSlot::Keyword`] that acts
+    /// as the leading keyword triggering the rule.
+    pub template: Vec<TemplateSlot>,
+}
+
+/// The registry of host extensions consulted by the parser.
+#[derive(Default)]
+pub struct OperatorRegistry {
+    infix: Vec<InfixOp>,
+    rules: Vec<SyntaxRule>,
+}
+
+impl OperatorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a custom infix operator.
+    pub fn register_infix(
+        &mut self,
+        name: impl Into<String>,
+        symbol: impl Into<String>,
+        binding_power: u32,
+        associativity: Associativity,
+    ) {
+        self.infix.push(InfixOp {
+            name: name.into(),
+            symbol: symbol.into(),
+            binding_power,
+            associativity,
+        });
+    }
+
+    /// Register a custom leading-keyword syntax rule.
+    pub fn register_rule(&mut self, name: impl Into<String>, template: Vec<TemplateSlot>) {
+        self.rules.push(SyntaxRule {
+            name: name.into(),
+            template,
+        });
+    }
+
+    /// Look up a registered infix operator by its trigger symbol.
+    pub fn infix(&self, symbol: &str) -> Option<&InfixOp> {
+        self.infix.iter().find(|op| op.symbol == symbol)
+    }
+
+    /// Look up a registered syntax rule by its leading keyword.
+    pub fn rule_for(&self, keyword: &str) -> Option<&SyntaxRule> {
+        self.rules.iter().find(|rule| {
+            matches!(rule.template.first(), Some(TemplateSlot::Keyword(kw)) if kw == keyword)
+        })
+    }
+
+    /// Build the [`Expr_::Custom`] node for a matched operator or rule.
+    pub fn make_custom(name: impl Into<String>, slots: Vec<Expr>, loc: Loc) -> Expr {
+        Expr {
+            loc,
+            val: Expr_::Custom(name.into(), slots),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_and_looks_up_infix_operator() {
+        let mut registry = OperatorRegistry::new();
+        registry.register_infix("pipe", "|>", 20, Associativity::Left);
+
+        let op = registry.infix("|>").expect("registered operator");
+        assert_eq!(op.name, "pipe");
+        assert_eq!(op.binding_power, 20);
+        assert_eq!(op.associativity, Associativity::Left);
+        assert!(registry.infix("??").is_none());
+    }
+
+    #[test]
+    fn registers_and_looks_up_rule_by_leading_keyword() {
+        let mut registry = OperatorRegistry::new();
+        registry.register_rule(
+            "unless",
+            vec![
+                TemplateSlot::Keyword("unless".into()),
+                TemplateSlot::Expr,
+                TemplateSlot::Keyword("then".into()),
+                TemplateSlot::Expr,
+            ],
+        );
+
+        assert_eq!(registry.rule_for("unless").expect("rule").name, "unless");
+        // "then" is a slot inside the rule, not its leading keyword.
+        assert!(registry.rule_for("then").is_none());
+    }
+
+    #[test]
+    fn make_custom_builds_the_node() {
+        let slot = Expr {
+            loc: Loc::new(0, 1),
+            val: Expr_::Id("x".into()),
+        };
+        let expr = OperatorRegistry::make_custom("pipe", vec![slot], Loc::new(0, 5));
+        match expr.val {
+            Expr_::Custom(name, slots) => {
+                assert_eq!(name, "pipe");
+                assert_eq!(slots.len(), 1);
+            }
+            _ => panic!("expected a Custom node"),
+        }
+    }
+}
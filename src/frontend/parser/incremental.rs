@@ -0,0 +1,137 @@
+//! Incremental parsing support for line-oriented REPLs.
+//!
+//! The REPL reads one line at a time but a block construct (`class`, `loop`,
+//! `for`, multi-line `def .. end`) spans several. [`LineBuffer`] accumulates
+//! source across `readline` calls until the parser returns a finished
+//! [`ParseResult`], distinguishing input that is merely unfinished from input
+//! that is genuinely malformed.
+//!
+//! This is the snapshot's IO-free half of the feature: [`LineBuffer::feed`]
+//! takes the parse function as a closure, so it is fully exercised by the
+//! tests below. The terminal loop that calls `readline`, picks the prompt and
+//! supplies the real parser lives in the `console` module, which is not part of
+//! this snapshot.
+
+use super::ast::Ast;
+
+/// The outcome of parsing a (possibly partial) source buffer.
+pub enum ParseResult {
+    /// A complete statement list.
+    Complete(Ast),
+    /// Parsing reached EOF with `open_scopes` block/delimiter contexts still
+    /// open — unfinished, but not malformed. The REPL keeps reading lines.
+    Incomplete { open_scopes: usize },
+    /// The input is genuinely malformed; `.0` is the error-recovered tree.
+    Error(Ast),
+}
+
+impl ParseResult {
+    /// Whether more input is needed to finish the current statement.
+    pub fn is_incomplete(&self) -> bool {
+        matches!(self, ParseResult::Incomplete { .. })
+    }
+}
+
+/// Accumulates source across `readline` calls until it parses to a finished
+/// [`Ast`], letting the caller show a continuation prompt in between.
+#[derive(Default)]
+pub struct LineBuffer {
+    source: String,
+}
+
+impl LineBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the buffer holds no pending input — `true` when it is *not*
+    /// mid-continuation, i.e. the next line starts a fresh statement.
+    pub fn is_empty(&self) -> bool {
+        self.source.is_empty()
+    }
+
+    /// The source accumulated so far.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Append `line` and re-parse with `parse`.
+    ///
+    /// On [`ParseResult::Incomplete`] the line is retained and `None` is
+    /// returned, so the caller prints a continuation prompt and reads again.
+    /// Otherwise the buffer is cleared and the finished result — complete or
+    /// error-recovered — is returned.
+    pub fn feed(
+        &mut self,
+        line: &str,
+        parse: impl FnOnce(&str) -> ParseResult,
+    ) -> Option<ParseResult> {
+        if !self.source.is_empty() {
+            self.source.push('\n');
+        }
+        self.source.push_str(line);
+        match parse(&self.source) {
+            ParseResult::Incomplete { .. } => None,
+            finished => {
+                self.source.clear();
+                Some(finished)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stand-in parser: input is complete once every `do` has a matching
+    /// `end`, an error once an `end` outnumbers the `do`s, and otherwise
+    /// incomplete with the count of still-open blocks.
+    fn parse(src: &str) -> ParseResult {
+        let opens = src.matches("do").count();
+        let closes = src.matches("end").count();
+        if closes > opens {
+            ParseResult::Error(Ast::default())
+        } else if opens > closes {
+            ParseResult::Incomplete {
+                open_scopes: opens - closes,
+            }
+        } else {
+            ParseResult::Complete(Ast::default())
+        }
+    }
+
+    #[test]
+    fn buffers_until_block_is_closed() {
+        let mut buf = LineBuffer::new();
+        assert!(buf.is_empty());
+
+        // Opening a block is incomplete: the line is retained.
+        assert!(buf.feed("for x in xs do", parse).is_none());
+        assert!(!buf.is_empty());
+        assert_eq!(buf.source(), "for x in xs do");
+
+        // A nested block leaves two scopes open.
+        assert!(buf.feed("loop do", parse).is_none());
+
+        // Closing both blocks completes the statement and clears the buffer.
+        let result = buf.feed("end end", parse).expect("completed");
+        assert!(matches!(result, ParseResult::Complete(_)));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn reports_open_scope_count_while_incomplete() {
+        let result = parse("loop do\nfor x in xs do");
+        assert!(result.is_incomplete());
+        assert!(matches!(result, ParseResult::Incomplete { open_scopes: 2 }));
+    }
+
+    #[test]
+    fn malformed_input_is_an_error_not_a_continuation() {
+        let mut buf = LineBuffer::new();
+        let result = buf.feed("end", parse).expect("finished, not buffered");
+        assert!(matches!(result, ParseResult::Error(_)));
+        assert!(buf.is_empty());
+    }
+}
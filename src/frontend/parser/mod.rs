@@ -0,0 +1,5 @@
+//! The Diatom parser and its syntax tree.
+
+pub mod ast;
+pub mod extension;
+pub mod incremental;
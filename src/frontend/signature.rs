@@ -0,0 +1,346 @@
+//! Signature-help queries over a parsed [`Ast`].
+//!
+//! This is the core primitive an LSP server uses for signature help: given a
+//! byte offset inside the source buffer, find the enclosing call, resolve the
+//! callee to its definition and report which argument the cursor is in.
+
+use super::parser::ast::{Ast, Const, Expr, Expr_, Stat, Stat_};
+use crate::diagnostic::Loc;
+
+/// Information about the call the cursor is currently inside.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallInfo {
+    /// Name of the invoked function or method.
+    pub name: String,
+    /// Parameter names pulled from the matching [`Expr_::Def`].
+    pub params: Vec<String>,
+    /// Index of the argument the cursor is currently in.
+    pub active_param: usize,
+}
+
+/// The enclosing call discovered by the walk: its name, the active argument
+/// index and whether it is a method call (which resolves against class methods
+/// rather than free functions).
+struct Enclosing {
+    name: String,
+    active_param: usize,
+    is_method: bool,
+}
+
+/// Return signature help for the call enclosing `offset`, if any.
+///
+/// Walks `ast` for the innermost [`Expr_::Call`]/[`Expr_::MethodCall`] whose
+/// span contains `offset`, resolves its callee name to the matching
+/// [`Expr_::Def`] and counts how many arguments precede the cursor.
+pub fn call_info(ast: &Ast, offset: usize) -> Option<CallInfo> {
+    let mut found = None;
+    for stat in &ast.statements {
+        walk_stat(stat, offset, &mut found);
+    }
+    let Enclosing {
+        name,
+        active_param,
+        is_method,
+    } = found?;
+    let params = resolve_params(ast, &name, is_method).unwrap_or_default();
+    Some(CallInfo {
+        name,
+        params,
+        active_param,
+    })
+}
+
+fn loc_contains(loc: &Loc, offset: usize) -> bool {
+    loc.start <= offset && offset < loc.end
+}
+
+/// Name of a callee expression, when it is a plain identifier.
+fn callee_name(expr: &Expr) -> Option<String> {
+    match &expr.val {
+        Expr_::Id(name) => Some(name.clone()),
+        Expr_::Parentheses(inner) => callee_name(inner),
+        _ => None,
+    }
+}
+
+/// Index of the argument `offset` falls into, given the argument spans.
+///
+/// When the cursor sits past the last typed argument — the usual "typing the
+/// next argument" case — this returns `args.len()`, pointing at the argument
+/// being entered rather than the last one already written.
+fn active_arg(args: &[Expr], offset: usize) -> usize {
+    args.iter()
+        .position(|arg| offset < arg.loc.end)
+        .unwrap_or(args.len())
+}
+
+fn record_call(expr: &Expr, offset: usize, out: &mut Option<Enclosing>) {
+    // The innermost enclosing call wins: children are visited after the
+    // parent, so a later write overrides an outer match.
+    match &expr.val {
+        Expr_::Call(callee, args) => {
+            if let Some(name) = callee_name(callee) {
+                *out = Some(Enclosing {
+                    name,
+                    active_param: active_arg(args, offset),
+                    is_method: false,
+                });
+            }
+            walk_expr(callee, offset, out);
+            for arg in args {
+                walk_expr(arg, offset, out);
+            }
+        }
+        Expr_::MethodCall(receiver, method, args) => {
+            *out = Some(Enclosing {
+                name: method.clone(),
+                active_param: active_arg(args, offset),
+                is_method: true,
+            });
+            walk_expr(receiver, offset, out);
+            for arg in args {
+                walk_expr(arg, offset, out);
+            }
+        }
+        _ => unreachable!("record_call only handles call expressions"),
+    }
+}
+
+fn walk_expr(expr: &Expr, offset: usize, out: &mut Option<Enclosing>) {
+    if !loc_contains(&expr.loc, offset) {
+        return;
+    }
+    match &expr.val {
+        Expr_::Call(..) | Expr_::MethodCall(..) => record_call(expr, offset, out),
+        Expr_::Block(body) => body.iter().for_each(|s| walk_stat(s, offset, out)),
+        Expr_::If(exprs) => exprs.iter().for_each(|e| walk_expr(e, offset, out)),
+        Expr_::Prefix(_, inner)
+        | Expr_::Parentheses(inner)
+        | Expr_::Cast(inner, _)
+        | Expr_::Assign(_, inner) => walk_expr(inner, offset, out),
+        Expr_::Index(a, b) | Expr_::Infix(_, a, b) => {
+            walk_expr(a, offset, out);
+            walk_expr(b, offset, out);
+        }
+        Expr_::Def(_, _, body, binds) => {
+            body.iter().for_each(|s| walk_stat(s, offset, out));
+            binds.iter().for_each(|(_, e)| walk_expr(e, offset, out));
+        }
+        Expr_::Custom(_, slots) => slots.iter().for_each(|e| walk_expr(e, offset, out)),
+        Expr_::Const(c) => walk_const(c, offset, out),
+        Expr_::Id(_) | Expr_::Error => {}
+    }
+}
+
+fn walk_const(c: &Const, offset: usize, out: &mut Option<Enclosing>) {
+    match c {
+        Const::List(items) | Const::Set(items) => {
+            items.iter().for_each(|e| walk_expr(e, offset, out))
+        }
+        Const::Dict(keys, vals) => {
+            keys.iter().for_each(|e| walk_expr(e, offset, out));
+            vals.iter().for_each(|e| walk_expr(e, offset, out));
+        }
+        _ => {}
+    }
+}
+
+fn walk_stat(stat: &Stat, offset: usize, out: &mut Option<Enclosing>) {
+    match &stat.val {
+        Stat_::Expr(expr) => walk_expr(expr, offset, out),
+        Stat_::Return(Some(expr)) => walk_expr(expr, offset, out),
+        Stat_::Class(_, _, methods) => methods.iter().for_each(|e| walk_expr(e, offset, out)),
+        Stat_::Loop(cond, body) => {
+            if let Some(cond) = cond {
+                walk_expr(cond, offset, out);
+            }
+            body.iter().for_each(|s| walk_stat(s, offset, out));
+        }
+        Stat_::For(_, iter, body) => {
+            // The binding side is a pattern and contains no call expressions.
+            walk_expr(iter, offset, out);
+            body.iter().for_each(|s| walk_stat(s, offset, out));
+        }
+        Stat_::Return(None) | Stat_::Continue | Stat_::Break | Stat_::Error => {}
+    }
+}
+
+/// Parameter names of a `def name(..)` expression.
+fn def_params(expr: &Expr, name: &str) -> Option<Vec<String>> {
+    if let Expr_::Def(Some(def_name), params, ..) = &expr.val {
+        if def_name == name {
+            return Some(params.iter().map(|(p, _)| p.clone()).collect());
+        }
+    }
+    None
+}
+
+/// Resolve `name` to its parameter list.
+///
+/// Method calls are resolved against the methods declared inside any
+/// [`Stat_::Class`]; free calls are resolved against `def`s in statement
+/// position, including those nested inside blocks, loops and function bodies.
+/// The receiver type is not tracked, so the first method of that name wins.
+fn resolve_params(ast: &Ast, name: &str, is_method: bool) -> Option<Vec<String>> {
+    if is_method {
+        let mut result = None;
+        for stat in &ast.statements {
+            resolve_method(stat, name, &mut result);
+            if result.is_some() {
+                break;
+            }
+        }
+        result
+    } else {
+        let mut result = None;
+        for stat in &ast.statements {
+            resolve_function(stat, name, &mut result);
+            if result.is_some() {
+                break;
+            }
+        }
+        result
+    }
+}
+
+fn resolve_method(stat: &Stat, name: &str, out: &mut Option<Vec<String>>) {
+    if out.is_some() {
+        return;
+    }
+    match &stat.val {
+        Stat_::Class(_, _, methods) => {
+            for method in methods {
+                if let Some(params) = def_params(method, name) {
+                    *out = Some(params);
+                    return;
+                }
+            }
+        }
+        Stat_::Expr(expr) => match &expr.val {
+            Expr_::Def(_, _, body, _) | Expr_::Block(body) => {
+                body.iter().for_each(|s| resolve_method(s, name, out))
+            }
+            _ => {}
+        },
+        Stat_::Loop(_, body) | Stat_::For(_, _, body) => {
+            body.iter().for_each(|s| resolve_method(s, name, out))
+        }
+        _ => {}
+    }
+}
+
+fn resolve_function(stat: &Stat, name: &str, out: &mut Option<Vec<String>>) {
+    if out.is_some() {
+        return;
+    }
+    match &stat.val {
+        Stat_::Expr(expr) => {
+            if let Some(params) = def_params(expr, name) {
+                *out = Some(params);
+                return;
+            }
+            // Descend into nested defs declared inside a function body or block.
+            if let Expr_::Def(_, _, body, _) | Expr_::Block(body) = &expr.val {
+                body.iter().for_each(|s| resolve_function(s, name, out));
+            }
+        }
+        Stat_::Loop(_, body) | Stat_::For(_, _, body) => {
+            body.iter().for_each(|s| resolve_function(s, name, out))
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::Loc;
+
+    fn spanned(start: usize, end: usize, val: Expr_) -> Expr {
+        Expr {
+            loc: Loc::new(start, end),
+            val,
+        }
+    }
+
+    fn stat(val: Stat_) -> Stat {
+        Stat {
+            loc: Loc::new(0, 0),
+            val,
+        }
+    }
+
+    fn untyped(names: &[&str]) -> Vec<(String, Option<crate::frontend::parser::ast::_Type>)> {
+        names.iter().map(|n| ((*n).to_string(), None)).collect()
+    }
+
+    /// `def foo(a, b) end` followed by `foo(1, 2)` with `foo` spanning 18..21
+    /// and the two argument literals at 22..23 and 25..26.
+    fn function_ast() -> Ast {
+        let def = stat(Stat_::Expr(spanned(
+            0,
+            17,
+            Expr_::Def(Some("foo".into()), untyped(&["a", "b"]), vec![], vec![]),
+        )));
+        let call = stat(Stat_::Expr(spanned(
+            18,
+            27,
+            Expr_::Call(
+                Box::new(spanned(18, 21, Expr_::Id("foo".into()))),
+                vec![
+                    spanned(22, 23, Expr_::Const(Const::Int(1))),
+                    spanned(25, 26, Expr_::Const(Const::Int(2))),
+                ],
+            ),
+        )));
+        Ast {
+            statements: vec![def, call],
+        }
+    }
+
+    #[test]
+    fn resolves_function_params_and_active_arg() {
+        let ast = function_ast();
+        let info = call_info(&ast, 22).expect("inside first argument");
+        assert_eq!(info.name, "foo");
+        assert_eq!(info.params, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(info.active_param, 0);
+
+        assert_eq!(call_info(&ast, 25).unwrap().active_param, 1);
+    }
+
+    #[test]
+    fn active_param_points_at_argument_being_typed() {
+        let ast = function_ast();
+        // Offset 26 sits past the last written argument's span (ends at 26):
+        // the cursor is typing a third argument.
+        assert_eq!(call_info(&ast, 26).unwrap().active_param, 2);
+    }
+
+    #[test]
+    fn resolves_method_against_class_methods() {
+        // class P ... def scale(factor) end end ; obj.scale(2)
+        let method = spanned(
+            0,
+            10,
+            Expr_::Def(Some("scale".into()), untyped(&["factor"]), vec![], vec![]),
+        );
+        let class = stat(Stat_::Class("P".into(), vec![], vec![method]));
+        let call = stat(Stat_::Expr(spanned(
+            20,
+            33,
+            Expr_::MethodCall(
+                Box::new(spanned(20, 23, Expr_::Id("obj".into()))),
+                "scale".into(),
+                vec![spanned(30, 31, Expr_::Const(Const::Int(2)))],
+            ),
+        )));
+        let ast = Ast {
+            statements: vec![class, call],
+        };
+        let info = call_info(&ast, 30).expect("inside the method argument");
+        assert_eq!(info.name, "scale");
+        assert_eq!(info.params, vec!["factor".to_string()]);
+        assert_eq!(info.active_param, 0);
+    }
+}
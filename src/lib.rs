@@ -7,17 +7,20 @@
 //!
 //! For the syntax specification see [The Book(WIP)]().
 //!
-//! ## How to use this interpreter
+//! ## Scope of this snapshot
 //!
-//! See examples in [Parser].
+//! This source snapshot contains the language *frontend*: the [`Ast`] produced
+//! by the parser and the source-level query APIs ([`call_info`]) built on top
+//! of it. The parser itself, the `backend`/`vm` evaluator and the interactive
+//! `console` REPL are
+//! maintained in modules that are not part of this snapshot; the AST carries
+//! the data those layers consume (method dispatch, type ascriptions, binding
+//! patterns, host syntax extensions).
 //!
-//!
-mod backend;
-mod console;
+//! [`Ast`]: frontend::parser::ast::Ast
+
 mod diagnostic;
 mod frontend;
-pub use console::Console;
-pub use frontend::Parser;
 
-#[cfg(feature = "vm")]
-pub use backend::{AsmFile, VM, VM_VERSION};
+pub use frontend::parser::{ast, extension, incremental};
+pub use frontend::{call_info, CallInfo};